@@ -0,0 +1,52 @@
+//! Pre-parses the bundled IANA CSV snapshot into a `bincode` blob at
+//! compile time, so `PortDescription::from_prebuilt()` can skip the CSV
+//! parser at runtime.
+//!
+//! This deliberately does not depend on the crate's own types - build
+//! scripts compile before the crate they build, so the intermediate
+//! format here is a plain tuple: `(service_name, port_number,
+//! transport_protocol, description)`. Fields are still deserialized by
+//! CSV header name (not position), matching `PortDescEntry`'s own
+//! `#[serde(rename = ...)]` headers, so a reordered IANA CSV can't
+//! silently desync this index from `default()`'s parser.
+use std::{env, fs, path::Path};
+
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Row {
+    #[serde(rename = "Service Name")]
+    service_name: String,
+    #[serde(rename = "Port Number", deserialize_with = "csv::invalid_option")]
+    port_number: Option<u16>,
+    #[serde(rename = "Transport Protocol")]
+    transport_protocol: Option<String>,
+    #[serde(rename = "Description")]
+    description: String,
+}
+
+type Record = (String, Option<u16>, Option<String>, String);
+
+fn main() {
+    println!("cargo:rerun-if-changed=assets/service-names-port-numbers.csv");
+
+    let csv_text = fs::read_to_string("assets/service-names-port-numbers.csv")
+        .expect("ERROR: cannot read assets/service-names-port-numbers.csv for prebuilt index");
+
+    let records: Vec<Record> = csv::Reader::from_reader(csv_text.as_bytes())
+        .deserialize::<Row>()
+        .filter_map(|r| r.ok())
+        .map(|r| {
+            (
+                r.service_name,
+                r.port_number,
+                r.transport_protocol.filter(|s| !s.is_empty()),
+                r.description,
+            )
+        })
+        .collect();
+
+    let bytes = bincode::serialize(&records).expect("ERROR: cannot serialize prebuilt index");
+    let out_path = Path::new(&env::var("OUT_DIR").unwrap()).join("index.bin");
+    fs::write(out_path, bytes).expect("ERROR: cannot write prebuilt index");
+}
@@ -0,0 +1,43 @@
+//! Compares the startup cost of `PortDescription::default()` (CSV parse)
+//! against `PortDescription::from_prebuilt()` (bincode deserialize of the
+//! index built by `build.rs`).
+//!
+//! Run with `cargo run --release --bin startup_bench`. Lives under
+//! `src/bin/` rather than `benches/` so cargo picks it up as a plain
+//! `[[bin]]` target - a `[[bench]]` target expects the unstable `#[bench]`
+//! harness, which a timed `fn main()` like this one can't use.
+use port_desc::PortDescription;
+use std::time::Instant;
+
+const RUNS: u32 = 100;
+
+fn main() {
+    let csv_start = Instant::now();
+    for _ in 0..RUNS {
+        PortDescription::default().expect("default() should parse the bundled CSV");
+    }
+    let csv_elapsed = csv_start.elapsed();
+
+    let prebuilt_start = Instant::now();
+    for _ in 0..RUNS {
+        PortDescription::from_prebuilt().expect("from_prebuilt() should decode the bincode index");
+    }
+    let prebuilt_elapsed = prebuilt_start.elapsed();
+
+    println!(
+        "default()       : {:>10.3?} total, {:>10.3?}/run",
+        csv_elapsed,
+        csv_elapsed / RUNS
+    );
+    println!(
+        "from_prebuilt() : {:>10.3?} total, {:>10.3?}/run",
+        prebuilt_elapsed,
+        prebuilt_elapsed / RUNS
+    );
+    assert!(
+        prebuilt_elapsed < csv_elapsed,
+        "from_prebuilt() should be faster than default() - got {:?} vs {:?}",
+        prebuilt_elapsed,
+        csv_elapsed
+    );
+}
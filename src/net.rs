@@ -0,0 +1,221 @@
+//! Online refresh of the IANA `service-names-port-numbers` registry.
+//!
+//! Gated behind the `net` feature so offline consumers are not forced to
+//! pull in an HTTP client or cache-directory detection.
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::Error;
+
+pub(crate) const IANA_CSV_URL: &str =
+    "https://www.iana.org/assignments/service-names-port-numbers/service-names-port-numbers.csv";
+
+fn cache_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|d| d.join("port_desc"))
+}
+
+pub(crate) fn csv_cache_path() -> Option<PathBuf> {
+    cache_dir().map(|d| d.join("service-names-port-numbers.csv"))
+}
+
+fn meta_cache_path() -> Option<PathBuf> {
+    cache_dir().map(|d| d.join("service-names-port-numbers.meta"))
+}
+
+#[derive(Default)]
+struct CacheMeta {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+fn read_meta_from(path: &Path) -> CacheMeta {
+    let Ok(text) = fs::read_to_string(path) else {
+        return CacheMeta::default();
+    };
+    let mut lines = text.lines();
+    CacheMeta {
+        etag: lines.next().filter(|s| !s.is_empty()).map(String::from),
+        last_modified: lines.next().filter(|s| !s.is_empty()).map(String::from),
+    }
+}
+
+fn write_meta_to(path: &Path, meta: &CacheMeta) -> Result<(), Error> {
+    let text = format!(
+        "{}\n{}\n",
+        meta.etag.as_deref().unwrap_or(""),
+        meta.last_modified.as_deref().unwrap_or("")
+    );
+    fs::write(path, text).map_err(|e| format!("ERROR: cannot write cache meta - {}", e))
+}
+
+/// Outcome of a conditional GET against the IANA registry, abstracted away
+/// from `ureq` so [`refresh_to`] can be driven by a fake fetcher in tests.
+pub(crate) enum FetchOutcome {
+    /// The registry changed; carries the new body and the `ETag` /
+    /// `Last-Modified` headers to cache for the next conditional request.
+    Modified {
+        body: String,
+        etag: Option<String>,
+        last_modified: Option<String>,
+    },
+    /// The server reported "not modified" for the cached `ETag` /
+    /// `Last-Modified` pair.
+    NotModified,
+}
+
+fn fetch(meta: &CacheMeta) -> Result<FetchOutcome, Error> {
+    let mut request = ureq::get(IANA_CSV_URL);
+    if let Some(etag) = &meta.etag {
+        request = request.set("If-None-Match", etag);
+    }
+    if let Some(last_modified) = &meta.last_modified {
+        request = request.set("If-Modified-Since", last_modified);
+    }
+
+    match request.call() {
+        Ok(response) => {
+            let etag = response.header("ETag").map(String::from);
+            let last_modified = response.header("Last-Modified").map(String::from);
+            let body = response
+                .into_string()
+                .map_err(|e| format!("ERROR: cannot read IANA response body - {}", e))?;
+            Ok(FetchOutcome::Modified {
+                body,
+                etag,
+                last_modified,
+            })
+        }
+        Err(ureq::Error::Status(304, _)) => Ok(FetchOutcome::NotModified),
+        Err(e) => Err(format!("ERROR: cannot fetch IANA registry - {}", e)),
+    }
+}
+
+/// Fetches the IANA registry, honouring a previously cached `ETag` /
+/// `Last-Modified` pair so an unchanged registry is a cheap no-op.
+/// Returns the CSV body, either freshly downloaded or read back from the
+/// on-disk cache when the server reports "not modified".
+pub(crate) fn refresh() -> Result<String, Error> {
+    let dir = cache_dir().ok_or_else(|| "ERROR: cannot determine cache directory".to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| format!("ERROR: cannot create cache dir - {}", e))?;
+    let csv_path = csv_cache_path().ok_or_else(|| "ERROR: cannot determine cache directory".to_string())?;
+    let meta_path = meta_cache_path().ok_or_else(|| "ERROR: cannot determine cache directory".to_string())?;
+    refresh_to(&csv_path, &meta_path, fetch)
+}
+
+/// Same as [`refresh`], but takes the cache paths and the HTTP layer as
+/// parameters so the caching and fallback logic can be exercised with a
+/// fake response, without touching the real cache directory.
+fn refresh_to(
+    csv_path: &Path,
+    meta_path: &Path,
+    fetch: impl FnOnce(&CacheMeta) -> Result<FetchOutcome, Error>,
+) -> Result<String, Error> {
+    let meta = read_meta_from(meta_path);
+    match fetch(&meta)? {
+        FetchOutcome::Modified {
+            body,
+            etag,
+            last_modified,
+        } => {
+            fs::write(csv_path, &body).map_err(|e| format!("ERROR: cannot write cache file - {}", e))?;
+            write_meta_to(
+                meta_path,
+                &CacheMeta {
+                    etag,
+                    last_modified,
+                },
+            )?;
+
+            Ok(body)
+        }
+        FetchOutcome::NotModified => {
+            fs::read_to_string(csv_path).map_err(|e| format!("ERROR: cached registry is missing - {}", e))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("port_desc_test_{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_read_meta_missing_file_defaults_empty() {
+        let dir = temp_dir("read_meta_missing");
+        let meta = read_meta_from(&dir.join("does-not-exist.meta"));
+        assert!(meta.etag.is_none());
+        assert!(meta.last_modified.is_none());
+    }
+
+    #[test]
+    fn test_write_then_read_meta_roundtrip() {
+        let dir = temp_dir("write_read_meta");
+        let path = dir.join("service-names-port-numbers.meta");
+        let meta = CacheMeta {
+            etag: Some(String::from("\"abc123\"")),
+            last_modified: Some(String::from("Wed, 01 Jan 2026 00:00:00 GMT")),
+        };
+        write_meta_to(&path, &meta).unwrap();
+
+        let read_back = read_meta_from(&path);
+        assert_eq!(read_back.etag.as_deref(), Some("\"abc123\""));
+        assert_eq!(
+            read_back.last_modified.as_deref(),
+            Some("Wed, 01 Jan 2026 00:00:00 GMT")
+        );
+    }
+
+    #[test]
+    fn test_refresh_to_writes_body_and_meta_on_modified() {
+        let dir = temp_dir("refresh_to_modified");
+        let csv_path = dir.join("service-names-port-numbers.csv");
+        let meta_path = dir.join("service-names-port-numbers.meta");
+
+        let body = refresh_to(&csv_path, &meta_path, |meta| {
+            assert!(meta.etag.is_none());
+            Ok(FetchOutcome::Modified {
+                body: String::from("service,port,protocol,description\n"),
+                etag: Some(String::from("\"abc123\"")),
+                last_modified: Some(String::from("Wed, 01 Jan 2026 00:00:00 GMT")),
+            })
+        })
+        .unwrap();
+
+        assert_eq!(body, "service,port,protocol,description\n");
+        assert_eq!(fs::read_to_string(&csv_path).unwrap(), body);
+        let meta = read_meta_from(&meta_path);
+        assert_eq!(meta.etag.as_deref(), Some("\"abc123\""));
+    }
+
+    #[test]
+    fn test_refresh_to_falls_back_to_cached_body_on_not_modified() {
+        let dir = temp_dir("refresh_to_not_modified");
+        let csv_path = dir.join("service-names-port-numbers.csv");
+        let meta_path = dir.join("service-names-port-numbers.meta");
+        fs::write(&csv_path, "service,port,protocol,description\n").unwrap();
+        write_meta_to(
+            &meta_path,
+            &CacheMeta {
+                etag: Some(String::from("\"abc123\"")),
+                last_modified: None,
+            },
+        )
+        .unwrap();
+
+        let body = refresh_to(&csv_path, &meta_path, |meta| {
+            assert_eq!(meta.etag.as_deref(), Some("\"abc123\""));
+            Ok(FetchOutcome::NotModified)
+        })
+        .unwrap();
+
+        assert_eq!(body, "service,port,protocol,description\n");
+    }
+}
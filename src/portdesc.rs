@@ -1,6 +1,14 @@
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, fs::read_to_string, path::Path};
 
+#[cfg(feature = "net")]
+mod net;
+mod portspec;
+mod rule;
+
+pub use portspec::{PortNumberSpec, PortRule, PortSpec, PortSpecs, PortState, Violation};
+pub use rule::{Rule, RuleAction};
+
 type Error = String;
 type PortsHashMaps = (
     HashMap<u16, PortDescEntry>,
@@ -9,7 +17,7 @@ type PortsHashMaps = (
     HashMap<u16, PortDescEntry>,
 );
 
-#[derive(Debug, Serialize, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum TransportProtocol {
     Tcp,
     Udp,
@@ -17,6 +25,21 @@ pub enum TransportProtocol {
     Dccp,
 }
 
+impl Serialize for TransportProtocol {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let s = match self {
+            TransportProtocol::Tcp => "tcp",
+            TransportProtocol::Udp => "udp",
+            TransportProtocol::Sctp => "sctp",
+            TransportProtocol::Dccp => "dccp",
+        };
+        serializer.serialize_str(s)
+    }
+}
+
 impl<'d> Deserialize<'d> for TransportProtocol {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -36,27 +59,63 @@ impl<'d> Deserialize<'d> for TransportProtocol {
     }
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PortRange {
+    WellKnown,
+    Registered,
+    Dynamic,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PortDescEntry {
     #[serde(rename = "Service Name")]
-    service_name: String,
+    pub(crate) service_name: String,
     #[serde(rename = "Port Number", deserialize_with = "csv::invalid_option")]
-    port_number: Option<u16>,
+    pub(crate) port_number: Option<u16>,
     #[serde(rename = "Transport Protocol")]
-    transport_protocol: Option<TransportProtocol>,
+    pub(crate) transport_protocol: Option<TransportProtocol>,
     #[serde(rename = "Description")]
-    description: String,
+    pub(crate) description: String,
+}
+
+impl PortDescEntry {
+    pub fn service_name(&self) -> &str {
+        &self.service_name
+    }
+
+    pub fn port_number(&self) -> Option<u16> {
+        self.port_number
+    }
+
+    pub fn transport_protocol(&self) -> Option<&TransportProtocol> {
+        self.transport_protocol.as_ref()
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct PortDescription {
     tcp_entries: HashMap<u16, PortDescEntry>,
     udp_entries: HashMap<u16, PortDescEntry>,
     dccp_entries: HashMap<u16, PortDescEntry>,
     sctp_entries: HashMap<u16, PortDescEntry>,
+    service_index: HashMap<String, Vec<(u16, TransportProtocol)>>,
 }
 
 impl PortDescription {
+    fn from_hashmaps(e: PortsHashMaps) -> Self {
+        let service_index = build_service_index(&e);
+        Self {
+            tcp_entries: e.0,
+            udp_entries: e.1,
+            dccp_entries: e.2,
+            sctp_entries: e.3,
+            service_index,
+        }
+    }
     pub fn default() -> Result<Self, Error> {
         //! Loads a default csv file
         //! that is downloaded from
@@ -71,12 +130,7 @@ impl PortDescription {
         //! ````
         let csv_text = include_str!("../assets/service-names-port-numbers.csv");
         match store_to_hashmaps(csv_text) {
-            Ok(e) => Ok(Self {
-                tcp_entries: e.0,
-                udp_entries: e.1,
-                dccp_entries: e.2,
-                sctp_entries: e.3,
-            }),
+            Ok(e) => Ok(Self::from_hashmaps(e)),
             Err(e) => Err(format!("Error: {}", e)),
         }
     }
@@ -95,12 +149,7 @@ impl PortDescription {
         //! ````
         if let Ok(csv_text) = read_to_string(csv_file.as_ref()) {
             match store_to_hashmaps(&csv_text) {
-                Ok(e) => Ok(Self {
-                    tcp_entries: e.0,
-                    udp_entries: e.1,
-                    dccp_entries: e.2,
-                    sctp_entries: e.3,
-                }),
+                Ok(e) => Ok(Self::from_hashmaps(e)),
                 Err(e) => Err(format!("Error: {}", e)),
             }
         } else {
@@ -111,6 +160,50 @@ impl PortDescription {
         }
     }
 
+    #[cfg(feature = "net")]
+    pub fn refresh_from_iana() -> Result<Self, Error> {
+        //! Downloads the latest registry from
+        //! https://www.iana.org/assignments/service-names-port-numbers/service-names-port-numbers.csv
+        //! and caches it under the user's cache directory. A previously
+        //! cached `ETag`/`Last-Modified` pair is sent along so the
+        //! download is a no-op when the registry has not changed.
+        //!
+        //! Requires the `net` feature.
+        let csv_text = net::refresh()?;
+        match store_to_hashmaps(&csv_text) {
+            Ok(e) => Ok(Self::from_hashmaps(e)),
+            Err(e) => Err(format!("Error: {}", e)),
+        }
+    }
+
+    pub fn from_cache_or_default() -> Result<Self, Error> {
+        //! Attempts to refresh the registry from the network (honouring
+        //! any cached `ETag`/`Last-Modified` pair, so an unchanged
+        //! registry is cheap), falls back to the last cached copy on
+        //! disk if the network is unavailable, and finally falls back to
+        //! the embedded CSV snapshot if no cache exists either.
+        //!
+        //! Without the `net` feature, neither the network nor the cache
+        //! is consulted and this is the same as
+        //! [`PortDescription::default`].
+        #[cfg(feature = "net")]
+        {
+            if let Ok(csv_text) = net::refresh() {
+                if let Ok(e) = store_to_hashmaps(&csv_text) {
+                    return Ok(Self::from_hashmaps(e));
+                }
+            }
+            if let Some(path) = net::csv_cache_path() {
+                if let Ok(csv_text) = read_to_string(path) {
+                    if let Ok(e) = store_to_hashmaps(&csv_text) {
+                        return Ok(Self::from_hashmaps(e));
+                    }
+                }
+            }
+        }
+        Self::default()
+    }
+
     pub fn get_port_service_name(
         &self,
         port_number: u16,
@@ -140,11 +233,136 @@ impl PortDescription {
         port_number: u16,
         transport_protocol: TransportProtocol,
     ) -> Option<&PortDescEntry> {
+        get_info(&port_number, self.entries_for(transport_protocol))
+    }
+
+    pub fn from_prebuilt() -> Result<Self, Error> {
+        //! Deserializes the compile-time-generated binary index built by
+        //! `build.rs` from the bundled CSV, skipping the CSV parser
+        //! entirely. Intended for short-lived CLIs where startup latency
+        //! matters; use [`PortDescription::default`] or
+        //! [`PortDescription::from_csv_file`] to refresh from a CSV
+        //! source instead. See `src/bin/startup_bench.rs` for a timed
+        //! comparison against [`PortDescription::default`].
+        const PREBUILT_INDEX: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/index.bin"));
+        type Record = (String, Option<u16>, Option<String>, String);
+        let raw: Vec<Record> =
+            bincode::deserialize(PREBUILT_INDEX).map_err(|e| format!("Error: {}", e))?;
+
+        let records: Vec<PortDescEntry> = raw
+            .into_iter()
+            .map(
+                |(service_name, port_number, transport_protocol, description)| PortDescEntry {
+                    service_name,
+                    port_number,
+                    transport_protocol: transport_protocol.and_then(|p| {
+                        match p.to_lowercase().as_str() {
+                            "tcp" => Some(TransportProtocol::Tcp),
+                            "udp" => Some(TransportProtocol::Udp),
+                            "sctp" => Some(TransportProtocol::Sctp),
+                            "dccp" => Some(TransportProtocol::Dccp),
+                            _ => None,
+                        }
+                    }),
+                    description,
+                },
+            )
+            .collect();
+
+        let tcp_entries = get_ports(TransportProtocol::Tcp, &records);
+        let udp_entries = get_ports(TransportProtocol::Udp, &records);
+        let dccp_entries = get_ports(TransportProtocol::Dccp, &records);
+        let sctp_entries = get_ports(TransportProtocol::Sctp, &records);
+
+        Ok(Self::from_hashmaps((
+            tcp_entries,
+            udp_entries,
+            dccp_entries,
+            sctp_entries,
+        )))
+    }
+
+    pub fn to_index_bytes(&self) -> Result<Vec<u8>, Error> {
+        //! Serializes the in-memory index to a compact binary blob with
+        //! `bincode`, so callers can cache it between short-lived runs.
+        bincode::serialize(self).map_err(|e| format!("Error: {}", e))
+    }
+
+    pub fn from_index_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        //! Inverse of [`PortDescription::to_index_bytes`].
+        bincode::deserialize(bytes).map_err(|e| format!("Error: {}", e))
+    }
+
+    pub fn find_by_service_name(&self, service_name: &str) -> Vec<&PortDescEntry> {
+        //! Looks up every port/protocol entry registered under an exact
+        //! service name, e.g. `https`.
+        match self.service_index.get(service_name) {
+            Some(hits) => hits
+                .iter()
+                .filter_map(|(port, protocol)| self.get_port_info(*port, protocol.clone()))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn search(&self, query: &str) -> Vec<&PortDescEntry> {
+        //! Case-insensitive substring search across both `service_name`
+        //! and `description` of every registered entry.
+        let query = query.to_lowercase();
+        self.all_entries()
+            .filter(|e| {
+                e.service_name.to_lowercase().contains(&query)
+                    || e.description.to_lowercase().contains(&query)
+            })
+            .collect()
+    }
+
+    fn all_entries(&self) -> impl Iterator<Item = &PortDescEntry> {
+        self.tcp_entries
+            .values()
+            .chain(self.udp_entries.values())
+            .chain(self.dccp_entries.values())
+            .chain(self.sctp_entries.values())
+    }
+
+    pub fn classify(port: u16) -> PortRange {
+        //! Buckets a port number into the IANA-defined ranges: well-known
+        //! (0-1023), registered (1024-49151) and dynamic/private
+        //! (49152-65535).
+        match port {
+            0..=1023 => PortRange::WellKnown,
+            1024..=49151 => PortRange::Registered,
+            _ => PortRange::Dynamic,
+        }
+    }
+
+    pub fn ports_in_range(
+        &self,
+        range: PortRange,
+        transport_protocol: TransportProtocol,
+    ) -> impl Iterator<Item = &PortDescEntry> {
+        self.entries_for(transport_protocol)
+            .values()
+            .filter(move |e| e.port_number.map(Self::classify) == Some(range))
+    }
+
+    pub fn entries_between(
+        &self,
+        start: u16,
+        end: u16,
+        transport_protocol: TransportProtocol,
+    ) -> impl Iterator<Item = &PortDescEntry> {
+        self.entries_for(transport_protocol)
+            .values()
+            .filter(move |e| e.port_number.is_some_and(|p| p >= start && p <= end))
+    }
+
+    fn entries_for(&self, transport_protocol: TransportProtocol) -> &HashMap<u16, PortDescEntry> {
         match transport_protocol {
-            TransportProtocol::Tcp => get_info(&port_number, &self.tcp_entries),
-            TransportProtocol::Udp => get_info(&port_number, &self.udp_entries),
-            TransportProtocol::Dccp => get_info(&port_number, &self.dccp_entries),
-            TransportProtocol::Sctp => get_info(&port_number, &self.sctp_entries),
+            TransportProtocol::Tcp => &self.tcp_entries,
+            TransportProtocol::Udp => &self.udp_entries,
+            TransportProtocol::Dccp => &self.dccp_entries,
+            TransportProtocol::Sctp => &self.sctp_entries,
         }
     }
 }
@@ -192,6 +410,27 @@ fn get_csv_deserialized(csv_text: &str) -> Result<Vec<PortDescEntry>, csv::Error
         .collect()
 }
 
+fn build_service_index(
+    e: &PortsHashMaps,
+) -> HashMap<String, Vec<(u16, TransportProtocol)>> {
+    let mut index: HashMap<String, Vec<(u16, TransportProtocol)>> = HashMap::new();
+    let protocols = [
+        (&e.0, TransportProtocol::Tcp),
+        (&e.1, TransportProtocol::Udp),
+        (&e.2, TransportProtocol::Dccp),
+        (&e.3, TransportProtocol::Sctp),
+    ];
+    for (hmap, protocol) in protocols {
+        for (port, entry) in hmap {
+            index
+                .entry(entry.service_name.clone())
+                .or_default()
+                .push((*port, protocol.clone()));
+        }
+    }
+    index
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -217,4 +456,98 @@ mod tests {
             assert!(false);
         }
     }
+
+    #[test]
+    fn test_from_cache_or_default() {
+        let port_desc = PortDescription::from_cache_or_default();
+        assert!(port_desc.is_ok());
+    }
+
+    #[test]
+    fn test_from_prebuilt_agrees_with_default() {
+        let prebuilt = PortDescription::from_prebuilt().unwrap();
+        let default = PortDescription::default().unwrap();
+        assert_eq!(
+            prebuilt.get_port_service_name(80, TransportProtocol::Tcp),
+            default.get_port_service_name(80, TransportProtocol::Tcp)
+        );
+        assert_eq!(
+            prebuilt
+                .find_by_service_name("https")
+                .iter()
+                .map(|e| e.port_number())
+                .collect::<Vec<_>>(),
+            default
+                .find_by_service_name("https")
+                .iter()
+                .map(|e| e.port_number())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_find_by_service_name() {
+        let port_desc = PortDescription::from_csv_file("assets/service-names-port-numbers.csv");
+        if let Ok(p) = port_desc {
+            let hits = p.find_by_service_name("https");
+            assert!(hits.iter().any(|e| e.port_number == Some(443)));
+        } else {
+            assert!(false);
+        }
+    }
+
+    #[test]
+    fn test_search() {
+        let port_desc = PortDescription::from_csv_file("assets/service-names-port-numbers.csv");
+        if let Ok(p) = port_desc {
+            let hits = p.search("http");
+            assert!(!hits.is_empty());
+        } else {
+            assert!(false);
+        }
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(PortDescription::classify(80), PortRange::WellKnown);
+        assert_eq!(PortDescription::classify(8080), PortRange::Registered);
+        assert_eq!(PortDescription::classify(50000), PortRange::Dynamic);
+    }
+
+    #[test]
+    fn test_ports_in_range() {
+        let port_desc = PortDescription::from_csv_file("assets/service-names-port-numbers.csv");
+        if let Ok(p) = port_desc {
+            let well_known: Vec<_> = p
+                .ports_in_range(PortRange::WellKnown, TransportProtocol::Tcp)
+                .collect();
+            assert!(well_known
+                .iter()
+                .all(|e| e.port_number.unwrap() <= 1023));
+        } else {
+            assert!(false);
+        }
+    }
+
+    #[test]
+    fn test_index_bytes_roundtrip() {
+        let port_desc = PortDescription::from_csv_file("assets/service-names-port-numbers.csv").unwrap();
+        let bytes = port_desc.to_index_bytes().unwrap();
+        let restored = PortDescription::from_index_bytes(&bytes).unwrap();
+        assert_eq!(
+            restored.get_port_service_name(80, TransportProtocol::Tcp),
+            "www-http"
+        );
+    }
+
+    #[test]
+    fn test_entries_between() {
+        let port_desc = PortDescription::from_csv_file("assets/service-names-port-numbers.csv");
+        if let Ok(p) = port_desc {
+            let entries: Vec<_> = p.entries_between(79, 81, TransportProtocol::Tcp).collect();
+            assert!(entries.iter().any(|e| e.port_number == Some(80)));
+        } else {
+            assert!(false);
+        }
+    }
 }
@@ -0,0 +1,345 @@
+//! YAML port-spec conformance checking.
+//!
+//! Modeled on nmap-analyze's `portspec`: a named host profile lists the
+//! ports it expects to be open or closed, and [`PortDescription::check`]
+//! compares that expectation against an observed set of open ports.
+use std::{path::Path, str::FromStr};
+
+use serde::Deserialize;
+
+use crate::{Error, PortDescEntry, PortDescription, TransportProtocol};
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct PortSpecs {
+    pub portspecs: Vec<PortSpec>,
+}
+
+impl PortSpecs {
+    pub fn from_yaml_str(yaml: &str) -> Result<Self, Error> {
+        //! Parses a YAML document listing named host profiles and the
+        //! ports each one expects to be open or closed.
+        serde_yaml::from_str(yaml).map_err(|e| format!("ERROR: port spec YAML cannot be parsed - {}", e))
+    }
+
+    pub fn from_yaml_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        //! Same as [`PortSpecs::from_yaml_str`] but reads the document
+        //! from a file on disk.
+        let text = std::fs::read_to_string(path.as_ref())
+            .map_err(|e| format!("ERROR: port spec file cannot be open - {}", e))?;
+        Self::from_yaml_str(&text)
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct PortSpec {
+    pub name: String,
+    pub ports: Vec<PortRule>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct PortRule {
+    pub port_number: Option<PortNumberSpec>,
+    pub transport_protocol: Option<TransportProtocol>,
+    pub service_name: Option<String>,
+    pub state: PortState,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PortState {
+    Open,
+    Closed,
+}
+
+/// A single port number or an inclusive range, e.g. `8000` or `8000-8100`.
+#[derive(Debug, Clone)]
+pub enum PortNumberSpec {
+    Single(u16),
+    Range(u16, u16),
+}
+
+impl PortNumberSpec {
+    pub(crate) fn expand(&self) -> Vec<u16> {
+        match self {
+            PortNumberSpec::Single(port) => vec![*port],
+            PortNumberSpec::Range(start, end) => (*start..=*end).collect(),
+        }
+    }
+}
+
+impl FromStr for PortNumberSpec {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        //! Parses a single port (`8000`) or an inclusive range
+        //! (`8000-8100`). An inverted range (`8100-8000`) is rejected
+        //! rather than silently expanding to an empty set, so callers
+        //! cannot end up resolving a rule to zero ports by typo.
+        match s.split_once('-') {
+            Some((start, end)) => {
+                let start: u16 = start
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("invalid port range - {}", s))?;
+                let end: u16 = end
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("invalid port range - {}", s))?;
+                if start > end {
+                    return Err(format!(
+                        "invalid port range (start > end) - {}",
+                        s
+                    ));
+                }
+                Ok(PortNumberSpec::Range(start, end))
+            }
+            None => s
+                .trim()
+                .parse()
+                .map(PortNumberSpec::Single)
+                .map_err(|_| format!("invalid port number - {}", s)),
+        }
+    }
+}
+
+impl<'d> Deserialize<'d> for PortNumberSpec {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'d>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Int(u16),
+            Str(String),
+        }
+
+        match Repr::deserialize(deserializer)? {
+            Repr::Int(port) => Ok(PortNumberSpec::Single(port)),
+            Repr::Str(s) => s.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+    /// A port that the spec expects closed was observed open.
+    UnexpectedlyOpen {
+        port_number: u16,
+        transport_protocol: TransportProtocol,
+        service_name: String,
+        description: String,
+    },
+    /// A port that the spec expects open was not observed.
+    UnexpectedlyClosed {
+        port_number: u16,
+        transport_protocol: TransportProtocol,
+        service_name: String,
+        description: String,
+    },
+    /// A rule's `service_name` does not resolve to any known port.
+    UnresolvedServiceName(String),
+    /// A rule specifies neither `port_number` nor `service_name`.
+    InvalidRule(String),
+}
+
+impl PortDescription {
+    pub fn check(&self, spec: &PortSpec, observed: &[(u16, TransportProtocol)]) -> Vec<Violation> {
+        //! Validates an observed set of open ports against a [`PortSpec`],
+        //! resolving `service_name` rules against the registry and
+        //! expanding port ranges before comparison.
+        let mut violations = Vec::new();
+        for rule in &spec.ports {
+            match self.resolve_rule_ports(rule) {
+                Ok(ports) => {
+                    for (port_number, transport_protocol) in ports {
+                        if let Some(v) =
+                            self.check_one(port_number, transport_protocol, rule.state, observed)
+                        {
+                            violations.push(v);
+                        }
+                    }
+                }
+                Err(v) => violations.push(v),
+            }
+        }
+        violations
+    }
+
+    fn check_one(
+        &self,
+        port_number: u16,
+        transport_protocol: TransportProtocol,
+        expected: PortState,
+        observed: &[(u16, TransportProtocol)],
+    ) -> Option<Violation> {
+        let is_open = observed
+            .iter()
+            .any(|(p, proto)| *p == port_number && *proto == transport_protocol);
+        let matches = matches!(
+            (expected, is_open),
+            (PortState::Open, true) | (PortState::Closed, false)
+        );
+        if matches {
+            return None;
+        }
+
+        let entry = self.get_port_info(port_number, transport_protocol.clone());
+        let service_name = entry.map(PortDescEntry::service_name).unwrap_or("").to_string();
+        let description = entry.map(PortDescEntry::description).unwrap_or("").to_string();
+
+        Some(match expected {
+            PortState::Closed => Violation::UnexpectedlyOpen {
+                port_number,
+                transport_protocol,
+                service_name,
+                description,
+            },
+            PortState::Open => Violation::UnexpectedlyClosed {
+                port_number,
+                transport_protocol,
+                service_name,
+                description,
+            },
+        })
+    }
+
+    fn resolve_rule_ports(&self, rule: &PortRule) -> Result<Vec<(u16, TransportProtocol)>, Violation> {
+        if let Some(service_name) = &rule.service_name {
+            let hits = self.find_by_service_name(service_name);
+            if hits.is_empty() {
+                return Err(Violation::UnresolvedServiceName(service_name.clone()));
+            }
+            return Ok(hits
+                .into_iter()
+                .filter(|e| {
+                    rule.transport_protocol.is_none()
+                        || e.transport_protocol() == rule.transport_protocol.as_ref()
+                })
+                .filter_map(|e| Some((e.port_number()?, e.transport_protocol()?.clone())))
+                .collect());
+        }
+
+        let port_number = rule
+            .port_number
+            .as_ref()
+            .ok_or_else(|| Violation::InvalidRule(String::from("rule has neither port_number nor service_name")))?;
+        let transport_protocol = rule.transport_protocol.clone().unwrap_or(TransportProtocol::Tcp);
+        Ok(port_number
+            .expand()
+            .into_iter()
+            .map(|p| (p, transport_protocol.clone()))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_unexpectedly_open() {
+        let port_desc =
+            PortDescription::from_csv_file("assets/service-names-port-numbers.csv").unwrap();
+        let spec = PortSpec {
+            name: String::from("web-server"),
+            ports: vec![PortRule {
+                port_number: None,
+                transport_protocol: None,
+                service_name: Some(String::from("https")),
+                state: PortState::Closed,
+            }],
+        };
+        let observed = vec![(443, TransportProtocol::Tcp)];
+        let violations = port_desc.check(&spec, &observed);
+        assert!(matches!(
+            violations.as_slice(),
+            [Violation::UnexpectedlyOpen { port_number: 443, .. }]
+        ));
+    }
+
+    #[test]
+    fn test_check_unresolved_service_name() {
+        let port_desc =
+            PortDescription::from_csv_file("assets/service-names-port-numbers.csv").unwrap();
+        let spec = PortSpec {
+            name: String::from("bogus"),
+            ports: vec![PortRule {
+                port_number: None,
+                transport_protocol: None,
+                service_name: Some(String::from("not-a-real-service")),
+                state: PortState::Open,
+            }],
+        };
+        let violations = port_desc.check(&spec, &[]);
+        assert_eq!(
+            violations,
+            vec![Violation::UnresolvedServiceName(String::from(
+                "not-a-real-service"
+            ))]
+        );
+    }
+
+    #[test]
+    fn test_check_service_name_scoped_to_transport_protocol() {
+        let port_desc =
+            PortDescription::from_csv_file("assets/service-names-port-numbers.csv").unwrap();
+        let spec = PortSpec {
+            name: String::from("dns-server"),
+            ports: vec![PortRule {
+                port_number: None,
+                transport_protocol: Some(TransportProtocol::Udp),
+                service_name: Some(String::from("domain")),
+                state: PortState::Open,
+            }],
+        };
+        let observed = vec![(53, TransportProtocol::Udp)];
+        let violations = port_desc.check(&spec, &observed);
+        assert!(
+            violations.is_empty(),
+            "expected no violations, since domain/tcp is out of scope for this udp-only rule - got {:?}",
+            violations
+        );
+    }
+
+    #[test]
+    fn test_port_number_range_expands() {
+        let range = PortNumberSpec::Range(8000, 8002);
+        assert_eq!(range.expand(), vec![8000, 8001, 8002]);
+    }
+
+    #[test]
+    fn test_port_number_spec_rejects_inverted_range() {
+        let err = "8100-8000".parse::<PortNumberSpec>().unwrap_err();
+        assert!(err.contains("start > end"));
+    }
+
+    #[test]
+    fn test_port_rule_rejects_inverted_range_yaml() {
+        let yaml = r#"
+portspecs:
+  - name: web-server
+    ports:
+      - port_number: "8100-8000"
+        state: open
+"#;
+        assert!(PortSpecs::from_yaml_str(yaml).is_err());
+    }
+
+    #[test]
+    fn test_check_invalid_rule() {
+        let port_desc =
+            PortDescription::from_csv_file("assets/service-names-port-numbers.csv").unwrap();
+        let spec = PortSpec {
+            name: String::from("malformed"),
+            ports: vec![PortRule {
+                port_number: None,
+                transport_protocol: None,
+                service_name: None,
+                state: PortState::Open,
+            }],
+        };
+        let violations = port_desc.check(&spec, &[]);
+        assert!(matches!(violations.as_slice(), [Violation::InvalidRule(_)]));
+    }
+}
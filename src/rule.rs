@@ -0,0 +1,168 @@
+//! Firewall-style rule parsing.
+//!
+//! Parsing happens in two stages, mirroring a common config-deserialization
+//! pattern: a rule line is first tokenized into an intermediate
+//! `HashMap<String, String>`, then [`Rule`] is `serde`-deserialized from
+//! that map so token validation and field typing stay separate.
+use std::{collections::HashMap, str::FromStr};
+
+use serde::{
+    de::value::{Error as ValueError, MapDeserializer},
+    Deserialize,
+};
+
+use crate::{Error, PortDescription, PortNumberSpec, TransportProtocol};
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct Rule {
+    pub action: RuleAction,
+    pub protocol: TransportProtocol,
+    pub dport: String,
+    /// Source address/CIDR this rule matches, or an empty string when the
+    /// rule line carried no `from` clause.
+    pub from: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RuleAction {
+    Accept,
+    Drop,
+}
+
+impl<'d> Deserialize<'d> for RuleAction {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'d>,
+    {
+        let s = String::deserialize(deserializer)?.to_lowercase();
+        match s.as_str() {
+            "accept" => Ok(RuleAction::Accept),
+            "drop" => Ok(RuleAction::Drop),
+            _ => Err(serde::de::Error::unknown_variant(&s, &["accept", "drop"])),
+        }
+    }
+}
+
+impl FromStr for Rule {
+    type Err = Error;
+
+    fn from_str(line: &str) -> Result<Self, Error> {
+        //! Parses a rule line such as
+        //! `ACCEPT tcp dport https,ssh,8000-8100 from 10.0.0.0/8`: the
+        //! first two tokens are the action and protocol, the rest are
+        //! `key value` pairs.
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if tokens.len() < 2 {
+            return Err(format!("ERROR: rule line is too short - {}", line));
+        }
+
+        let mut fields: HashMap<String, String> = HashMap::new();
+        fields.insert(String::from("action"), tokens[0].to_string());
+        fields.insert(String::from("protocol"), tokens[1].to_string());
+        fields.insert(String::from("from"), String::new());
+
+        let mut rest = tokens[2..].iter();
+        while let Some(key) = rest.next() {
+            let value = rest
+                .next()
+                .ok_or_else(|| format!("ERROR: rule line has a dangling key `{}` - {}", key, line))?;
+            fields.insert(key.to_string(), value.to_string());
+        }
+
+        Rule::deserialize(MapDeserializer::<_, ValueError>::new(fields.into_iter()))
+            .map_err(|e| format!("ERROR: cannot parse rule `{}` - {}", line, e))
+    }
+}
+
+impl Rule {
+    pub fn expand_dport(&self, port_desc: &PortDescription) -> Result<Vec<(u16, TransportProtocol)>, Error> {
+        //! Expands this rule's `dport` field into concrete ports,
+        //! resolving service-name aliases against `port_desc`.
+        port_desc.expand_dport(&self.dport, self.protocol.clone())
+    }
+}
+
+impl PortDescription {
+    pub fn expand_dport(
+        &self,
+        dport: &str,
+        transport_protocol: TransportProtocol,
+    ) -> Result<Vec<(u16, TransportProtocol)>, Error> {
+        //! Expands a `dport` field - a comma-separated mix of literal
+        //! port numbers, inclusive ranges (`8000-8100`), and service-name
+        //! aliases (`https`) - into a concrete list of ports, resolving
+        //! aliases against the registry. A single port or range is parsed
+        //! via [`PortNumberSpec`], so an inverted range (`8100-8000`)
+        //! errors here the same way it does in a YAML port spec, rather
+        //! than silently resolving to zero ports.
+        let mut ports = Vec::new();
+        for token in dport.split(',') {
+            let token = token.trim();
+            let is_range_or_number = match token.split_once('-') {
+                Some((start, end)) => start.trim().parse::<u16>().is_ok() && end.trim().parse::<u16>().is_ok(),
+                None => token.parse::<u16>().is_ok(),
+            };
+            if is_range_or_number {
+                let spec: PortNumberSpec = token.parse()?;
+                ports.extend(spec.expand().into_iter().map(|p| (p, transport_protocol.clone())));
+            } else {
+                let hits = self.find_by_service_name(token);
+                let resolved: Vec<(u16, TransportProtocol)> = hits
+                    .into_iter()
+                    .filter(|e| e.transport_protocol() == Some(&transport_protocol))
+                    .filter_map(|e| Some((e.port_number()?, transport_protocol.clone())))
+                    .collect();
+                if resolved.is_empty() {
+                    return Err(format!("ERROR: unknown service name in dport - {}", token));
+                }
+                ports.extend(resolved);
+            }
+        }
+        Ok(ports)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rule() {
+        let rule: Rule = "ACCEPT tcp dport https,ssh,8000-8100 from 10.0.0.0/8"
+            .parse()
+            .unwrap();
+        assert_eq!(rule.action, RuleAction::Accept);
+        assert_eq!(rule.protocol, TransportProtocol::Tcp);
+        assert_eq!(rule.dport, "https,ssh,8000-8100");
+        assert_eq!(rule.from, "10.0.0.0/8");
+    }
+
+    #[test]
+    fn test_expand_dport() {
+        let port_desc =
+            PortDescription::from_csv_file("assets/service-names-port-numbers.csv").unwrap();
+        let rule: Rule = "ACCEPT tcp dport https,8000-8002".parse().unwrap();
+        let ports = rule.expand_dport(&port_desc).unwrap();
+        assert!(ports.contains(&(443, TransportProtocol::Tcp)));
+        assert!(ports.contains(&(8000, TransportProtocol::Tcp)));
+        assert!(ports.contains(&(8001, TransportProtocol::Tcp)));
+        assert!(ports.contains(&(8002, TransportProtocol::Tcp)));
+    }
+
+    #[test]
+    fn test_expand_dport_rejects_inverted_range() {
+        let port_desc =
+            PortDescription::from_csv_file("assets/service-names-port-numbers.csv").unwrap();
+        let rule: Rule = "ACCEPT tcp dport 8100-8000".parse().unwrap();
+        assert!(rule.expand_dport(&port_desc).is_err());
+    }
+
+    #[test]
+    fn test_expand_dport_hyphenated_service_name() {
+        let port_desc =
+            PortDescription::from_csv_file("assets/service-names-port-numbers.csv").unwrap();
+        let rule: Rule = "ACCEPT tcp dport ftp-data".parse().unwrap();
+        let ports = rule.expand_dport(&port_desc).unwrap();
+        assert!(ports.contains(&(20, TransportProtocol::Tcp)));
+    }
+}